@@ -0,0 +1,167 @@
+// A JSON serializer, the write-side counterpart to the streaming parser.
+//
+// `Json` is a small owned value tree (the parser itself stays zero-copy and
+// event-based, so it has nothing like this); `Encoder` turns one into text,
+// either compact or pretty-printed with a configurable indent width,
+// mirroring librustc_serialize's JSON encoder.
+
+use std::io;
+
+// Numbers are kept as their original text, same as `Event::Number`, so
+// encoding never has to round-trip a value through a float.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    // A `Vec` rather than a map preserves member order, same as the order
+    // members are read off the wire by the parser.
+    Object(Vec<(String, Json)>),
+}
+
+pub struct Encoder {
+    indent: Option<usize>,
+}
+
+impl Encoder {
+    pub fn compact() -> Self {
+        Encoder { indent: None }
+    }
+
+    pub fn pretty(indent: usize) -> Self {
+        Encoder {
+            indent: Some(indent),
+        }
+    }
+
+    pub fn encode(&self, value: &Json) -> String {
+        let mut out = String::new();
+        self.write_value(&mut out, value, 0);
+        out
+    }
+
+    pub fn encode_to<W: io::Write>(&self, writer: &mut W, value: &Json) -> io::Result<()> {
+        writer.write_all(self.encode(value).as_bytes())
+    }
+
+    fn write_value(&self, out: &mut String, value: &Json, depth: usize) {
+        match value {
+            Json::Null => out.push_str("null"),
+            Json::Bool(true) => out.push_str("true"),
+            Json::Bool(false) => out.push_str("false"),
+            Json::Number(n) => out.push_str(n),
+            Json::String(s) => write_escaped_string(out, s),
+            Json::Array(items) => self.write_array(out, items, depth),
+            Json::Object(members) => self.write_object(out, members, depth),
+        }
+    }
+
+    fn write_array(&self, out: &mut String, items: &[Json], depth: usize) {
+        if items.is_empty() {
+            out.push_str("[]");
+            return;
+        }
+        out.push('[');
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            self.newline_indent(out, depth + 1);
+            self.write_value(out, item, depth + 1);
+        }
+        self.newline_indent(out, depth);
+        out.push(']');
+    }
+
+    fn write_object(&self, out: &mut String, members: &[(String, Json)], depth: usize) {
+        if members.is_empty() {
+            out.push_str("{}");
+            return;
+        }
+        out.push('{');
+        for (i, (key, value)) in members.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            self.newline_indent(out, depth + 1);
+            write_escaped_string(out, key);
+            out.push(':');
+            if self.indent.is_some() {
+                out.push(' ');
+            }
+            self.write_value(out, value, depth + 1);
+        }
+        self.newline_indent(out, depth);
+        out.push('}');
+    }
+
+    fn newline_indent(&self, out: &mut String, depth: usize) {
+        if let Some(width) = self.indent {
+            out.push('\n');
+            for _ in 0..width * depth {
+                out.push(' ');
+            }
+        }
+    }
+}
+
+fn write_escaped_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0c}' => out.push_str("\\f"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 || (c as u32) == 0x7f => {
+                out.push_str(&format!("\\u{:04x}", c as u32));
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Encoder, Json};
+
+    #[test]
+    fn encodes_compact_object() {
+        let value = Json::Object(vec![
+            ("a".to_string(), Json::Number("1".to_string())),
+            ("b".to_string(), Json::Bool(true)),
+        ]);
+        assert_eq!(Encoder::compact().encode(&value), r#"{"a":1,"b":true}"#);
+    }
+
+    #[test]
+    fn encodes_pretty_nested_document() {
+        let value = Json::Object(vec![(
+            "items".to_string(),
+            Json::Array(vec![Json::Number("1".to_string()), Json::Null]),
+        )]);
+        let expected = "{\n  \"items\": [\n    1,\n    null\n  ]\n}";
+        assert_eq!(Encoder::pretty(2).encode(&value), expected);
+    }
+
+    #[test]
+    fn encodes_empty_containers_without_inner_whitespace() {
+        let value = Json::Array(vec![Json::Object(vec![]), Json::Array(vec![])]);
+        assert_eq!(Encoder::pretty(2).encode(&value), "[\n  {},\n  []\n]");
+    }
+
+    #[test]
+    fn escapes_control_characters_and_quotes() {
+        let value = Json::String("line1\nline2\t\"quoted\"\u{1}".to_string());
+        assert_eq!(
+            Encoder::compact().encode(&value),
+            "\"line1\\nline2\\t\\\"quoted\\\"\\u0001\""
+        );
+    }
+}
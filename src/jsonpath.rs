@@ -0,0 +1,341 @@
+// A JSONPath query layer over the event stream produced by `Parser::feed`.
+//
+// Only a subset of JSONPath is supported: `$`, `.key`, `['key']`, `[index]`,
+// `[*]` and the recursive `..`. A `Selector` is compiled once from a path
+// string and then driven by a `PathMatcher`, which tracks the current
+// position inside the document as a stack synchronized with the container
+// events (`StartObject`/`EndObject`/`StartArray`/`EndArray`) rather than
+// materializing the document itself.
+
+use crate::Event;
+
+#[derive(Debug, PartialEq)]
+pub enum PathError {
+    WrongFormat(usize),
+}
+
+#[derive(Debug, PartialEq)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+    Wildcard,
+}
+
+#[derive(Debug, PartialEq)]
+struct Step {
+    recursive: bool,
+    segment: PathSegment,
+}
+
+// A path element of a concrete location inside a document, as opposed to a
+// `PathSegment`, which is a (possibly wildcard/recursive) selector step.
+#[derive(Debug, PartialEq, Clone)]
+pub enum PathElement {
+    Key(String),
+    Index(usize),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Selector {
+    steps: Vec<Step>,
+}
+
+impl Selector {
+    pub fn compile(path: &str) -> Result<Selector, PathError> {
+        let chars: Vec<char> = path.chars().collect();
+        if chars.first() != Some(&'$') {
+            return Err(PathError::WrongFormat(0));
+        }
+        let mut i = 1;
+        let mut steps = Vec::new();
+        while i < chars.len() {
+            match chars[i] {
+                '.' => {
+                    i += 1;
+                    let recursive = chars.get(i) == Some(&'.');
+                    if recursive {
+                        i += 1;
+                    }
+                    if chars.get(i) == Some(&'[') {
+                        let (segment, next) = parse_bracket(&chars, i)?;
+                        steps.push(Step { recursive, segment });
+                        i = next;
+                    } else {
+                        let (key, next) = parse_bare_key(&chars, i)?;
+                        steps.push(Step {
+                            recursive,
+                            segment: PathSegment::Key(key),
+                        });
+                        i = next;
+                    }
+                }
+                '[' => {
+                    let (segment, next) = parse_bracket(&chars, i)?;
+                    steps.push(Step {
+                        recursive: false,
+                        segment,
+                    });
+                    i = next;
+                }
+                _ => return Err(PathError::WrongFormat(i)),
+            }
+        }
+        Ok(Selector { steps })
+    }
+
+    fn matches(&self, path: &[PathElement]) -> bool {
+        Self::matches_steps(&self.steps, path)
+    }
+
+    fn matches_steps(steps: &[Step], path: &[PathElement]) -> bool {
+        let (step, rest) = match steps.split_first() {
+            Some(split) => split,
+            None => return path.is_empty(),
+        };
+        if !step.recursive {
+            return match path.split_first() {
+                Some((head, tail)) if Self::segment_matches(&step.segment, head) => {
+                    Self::matches_steps(rest, tail)
+                }
+                _ => false,
+            };
+        }
+        // A recursive step may match the selector's next segment at any
+        // depth from here on, including not at all (zero hops).
+        for depth in 0..path.len() {
+            if Self::segment_matches(&step.segment, &path[depth])
+                && Self::matches_steps(rest, &path[depth + 1..])
+            {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn segment_matches(segment: &PathSegment, element: &PathElement) -> bool {
+        match (segment, element) {
+            (PathSegment::Wildcard, _) => true,
+            (PathSegment::Key(k), PathElement::Key(ek)) => k == ek,
+            (PathSegment::Index(i), PathElement::Index(ei)) => i == ei,
+            _ => false,
+        }
+    }
+}
+
+fn parse_bare_key(chars: &[char], start: usize) -> Result<(String, usize), PathError> {
+    let mut i = start;
+    while i < chars.len() && chars[i] != '.' && chars[i] != '[' {
+        i += 1;
+    }
+    if i == start {
+        return Err(PathError::WrongFormat(start));
+    }
+    Ok((chars[start..i].iter().collect(), i))
+}
+
+fn parse_bracket(chars: &[char], start: usize) -> Result<(PathSegment, usize), PathError> {
+    let content_start = start + 1;
+    let mut i = content_start;
+    while i < chars.len() && chars[i] != ']' {
+        i += 1;
+    }
+    if i >= chars.len() {
+        return Err(PathError::WrongFormat(start));
+    }
+    let content: String = chars[content_start..i].iter().collect();
+    let end = i + 1;
+    let quoted = (content.starts_with('\'') && content.ends_with('\'') && content.len() >= 2)
+        || (content.starts_with('"') && content.ends_with('"') && content.len() >= 2);
+    let segment = if content == "*" {
+        PathSegment::Wildcard
+    } else if quoted {
+        PathSegment::Key(content[1..content.len() - 1].to_string())
+    } else if let Ok(index) = content.parse::<usize>() {
+        PathSegment::Index(index)
+    } else {
+        return Err(PathError::WrongFormat(content_start));
+    };
+    Ok((segment, end))
+}
+
+// Tracks which container each open frame is, so array elements can be
+// numbered and object members identified by their most recent key.
+enum Frame {
+    Object,
+    Array(usize),
+}
+
+// Drives a compiled `Selector` against the event stream emitted by
+// `Parser::feed`, maintaining the current path as a stack synchronized with
+// the open containers rather than building the document in memory.
+pub struct PathMatcher {
+    selector: Selector,
+    frames: Vec<Frame>,
+    path: Vec<PathElement>,
+    pending_key: Option<String>,
+}
+
+impl PathMatcher {
+    pub fn new(selector: Selector) -> Self {
+        PathMatcher {
+            selector,
+            frames: Vec::new(),
+            path: Vec::new(),
+            pending_key: None,
+        }
+    }
+
+    // Feeds one parser event, returning whether the value (or container)
+    // this event introduces sits at a path matching the selector.
+    pub fn observe<'s>(&mut self, event: &Event<'s>) -> bool {
+        match event {
+            Event::Key(k) => {
+                self.pending_key = Some((*k).to_string());
+                false
+            }
+            Event::StartObject => self.enter(Frame::Object),
+            Event::StartArray => self.enter(Frame::Array(0)),
+            Event::EndObject | Event::EndArray => {
+                self.exit();
+                false
+            }
+            Event::String(_) | Event::Number(_) | Event::Bool(_) | Event::Null => self.leaf(),
+        }
+    }
+
+    fn enter(&mut self, frame: Frame) -> bool {
+        if !self.frames.is_empty() {
+            let element = self.next_element();
+            self.path.push(element);
+        }
+        let matched = self.selector.matches(&self.path);
+        self.frames.push(frame);
+        matched
+    }
+
+    fn leaf(&mut self) -> bool {
+        let pushed = !self.frames.is_empty();
+        if pushed {
+            let element = self.next_element();
+            self.path.push(element);
+        }
+        let matched = self.selector.matches(&self.path);
+        if pushed {
+            self.path.pop();
+        }
+        self.advance_parent();
+        matched
+    }
+
+    fn exit(&mut self) {
+        if self.frames.len() > 1 {
+            self.path.pop();
+        }
+        self.frames.pop();
+        self.advance_parent();
+    }
+
+    fn next_element(&mut self) -> PathElement {
+        match self.frames.last() {
+            Some(Frame::Array(i)) => PathElement::Index(*i),
+            _ => PathElement::Key(self.pending_key.take().unwrap_or_default()),
+        }
+    }
+
+    fn advance_parent(&mut self) {
+        if let Some(Frame::Array(i)) = self.frames.last_mut() {
+            *i += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PathMatcher, Selector};
+    use crate::{Event, Parser};
+
+    fn matched_numbers(path: &str, data: &str) -> Vec<String> {
+        let selector = Selector::compile(path).unwrap();
+        let mut matcher = PathMatcher::new(selector);
+        let mut parser = Parser::new();
+        let mut matched = Vec::new();
+        parser
+            .feed(data, |event| {
+                if matcher.observe(&event) {
+                    if let Event::Number(n) = event {
+                        matched.push(n.to_string());
+                    }
+                }
+            })
+            .unwrap();
+        matched
+    }
+
+    #[test]
+    fn matches_plain_object_key() {
+        let matched = matched_numbers("$.b", "{\"a\":1,\"b\":2}");
+        assert_eq!(matched, vec!["2"]);
+    }
+
+    #[test]
+    fn matches_bracket_key_and_index() {
+        let matched = matched_numbers("$['a'][1]", "{\"a\":[10,20,30]}");
+        assert_eq!(matched, vec!["20"]);
+    }
+
+    #[test]
+    fn matches_array_wildcard() {
+        let matched = matched_numbers("$.items[*]", "{\"items\":[1,2,3]}");
+        assert_eq!(matched, vec!["1", "2", "3"]);
+    }
+
+    #[test]
+    fn matches_recursive_descent() {
+        let matched = matched_numbers("$..price", "{\"a\":{\"price\":1},\"b\":[{\"price\":2},{\"x\":3}]}");
+        assert_eq!(matched, vec!["1", "2"]);
+    }
+
+    #[test]
+    fn matches_whole_container_at_a_path() {
+        let selector = Selector::compile("$.b").unwrap();
+        let mut matcher = PathMatcher::new(selector);
+        let mut parser = Parser::new();
+        let mut matched = Vec::new();
+        parser
+            .feed("{\"a\":1,\"b\":{\"c\":2}}", |event| {
+                if matcher.observe(&event) {
+                    matched.push(event);
+                }
+            })
+            .unwrap();
+        assert_eq!(matched, vec![Event::StartObject]);
+    }
+
+    #[test]
+    fn bare_root_selector_matches_only_the_root_value() {
+        // A bare number is only flushed by the explicit `finish` call, not
+        // by `feed` alone; see feed_emits_a_bare_scalar_and_then_ends.
+        let selector = Selector::compile("$").unwrap();
+        let mut matcher = PathMatcher::new(selector);
+        let mut parser = Parser::new();
+        let mut matched = Vec::new();
+        parser.feed("42", |_| {}).unwrap();
+        let event = parser.finish().unwrap().unwrap();
+        if matcher.observe(&event) {
+            if let Event::Number(n) = event {
+                matched.push(n.to_string());
+            }
+        }
+        assert_eq!(matched, vec!["42"]);
+    }
+
+    #[test]
+    fn compile_rejects_a_path_without_a_leading_dollar() {
+        assert!(Selector::compile("a.b").is_err());
+    }
+
+    #[test]
+    fn compile_rejects_an_unterminated_bracket() {
+        assert!(Selector::compile("$['a'").is_err());
+    }
+}
@@ -1,6 +1,8 @@
+use std::borrow::Cow;
 use std::cmp::PartialEq;
 
-use arrayvec::ArrayVec;
+pub mod encoder;
+pub mod jsonpath;
 
 #[derive(Debug, PartialEq, Copy, Clone)]
 enum JT {
@@ -13,17 +15,23 @@ enum JT {
     WhiteSpace,
     JString,
     JNumber,
+    JTrue,
+    JFalse,
+    JNull,
 }
 
+// `slice` is borrowed straight out of the input for zero-copy tokens, or
+// owned when it had to be reassembled in `Tokenizer::scratch` (an escaped
+// string, or a number/literal split across chunks) — either way its
+// lifetime is independent of the `&mut Tokenizer` borrow that produced it.
 #[derive(Debug, PartialEq)]
 struct JValues<'s> {
-    slice: &'s str,
+    slice: Cow<'s, str>,
     jt: JT,
 }
 
 #[derive(Debug, PartialEq)]
 enum TokenizerErrors {
-    EndOfData,
     NeedMoreData,
     WrongEscapeSequence(usize),
     WrongFormat(usize),
@@ -33,6 +41,7 @@ struct Tokenizer {
     scratch: std::string::String,
     state: TokenizerState,
     index: usize,
+    pending_high_surrogate: Option<u16>,
 }
 
 enum TokenizerState {
@@ -41,19 +50,115 @@ enum TokenizerState {
     StartEscaping,
     CopyingString,
     ReadingHex(u64, i8),
+    ExpectLowSurrogateBackslash,
+    ExpectLowSurrogateU,
+    ReadingNumberZeroCopy(NumberPhase),
+    ReadingNumberScratch(NumberPhase),
+    ReadingLiteral(&'static str, usize, JT),
 }
 
-impl<'s, 'scratch: 's> Tokenizer {
-    fn tokenize(&'scratch mut self, data: &'s str) -> Result<JValues<'s>, TokenizerErrors> {
+// Tracks where we are inside the JSON number grammar:
+// -? (0 | [1-9][0-9]*) (. [0-9]+)? ([eE] [+-]? [0-9]+)?
+#[derive(Debug, PartialEq, Copy, Clone)]
+enum NumberPhase {
+    Start,
+    Sign,
+    LeadingZero,
+    IntDigits,
+    DotSeen,
+    FracDigits,
+    ExpSeen,
+    ExpSignSeen,
+    ExpDigits,
+}
+
+enum NumberStep {
+    Continue(NumberPhase),
+    Terminate,
+    Invalid,
+}
+
+impl NumberPhase {
+    // Whether the digits seen so far already form a complete, valid number.
+    fn is_complete(self) -> bool {
+        matches!(
+            self,
+            NumberPhase::LeadingZero
+                | NumberPhase::IntDigits
+                | NumberPhase::FracDigits
+                | NumberPhase::ExpDigits
+        )
+    }
+    fn step(self, c: char) -> NumberStep {
+        match (self, c) {
+            (NumberPhase::Start, '-') => NumberStep::Continue(NumberPhase::Sign),
+            (NumberPhase::Start, '0') => NumberStep::Continue(NumberPhase::LeadingZero),
+            (NumberPhase::Start, '1'..='9') => NumberStep::Continue(NumberPhase::IntDigits),
+            (NumberPhase::Start, _) => NumberStep::Invalid,
+
+            (NumberPhase::Sign, '0') => NumberStep::Continue(NumberPhase::LeadingZero),
+            (NumberPhase::Sign, '1'..='9') => NumberStep::Continue(NumberPhase::IntDigits),
+            (NumberPhase::Sign, _) => NumberStep::Invalid,
+
+            (NumberPhase::LeadingZero, '.') => NumberStep::Continue(NumberPhase::DotSeen),
+            (NumberPhase::LeadingZero, 'e') | (NumberPhase::LeadingZero, 'E') => {
+                NumberStep::Continue(NumberPhase::ExpSeen)
+            }
+            (NumberPhase::LeadingZero, _) => NumberStep::Terminate,
+
+            (NumberPhase::IntDigits, '0'..='9') => NumberStep::Continue(NumberPhase::IntDigits),
+            (NumberPhase::IntDigits, '.') => NumberStep::Continue(NumberPhase::DotSeen),
+            (NumberPhase::IntDigits, 'e') | (NumberPhase::IntDigits, 'E') => {
+                NumberStep::Continue(NumberPhase::ExpSeen)
+            }
+            (NumberPhase::IntDigits, _) => NumberStep::Terminate,
+
+            (NumberPhase::DotSeen, '0'..='9') => NumberStep::Continue(NumberPhase::FracDigits),
+            (NumberPhase::DotSeen, _) => NumberStep::Invalid,
+
+            (NumberPhase::FracDigits, '0'..='9') => NumberStep::Continue(NumberPhase::FracDigits),
+            (NumberPhase::FracDigits, 'e') | (NumberPhase::FracDigits, 'E') => {
+                NumberStep::Continue(NumberPhase::ExpSeen)
+            }
+            (NumberPhase::FracDigits, _) => NumberStep::Terminate,
+
+            (NumberPhase::ExpSeen, '+') | (NumberPhase::ExpSeen, '-') => {
+                NumberStep::Continue(NumberPhase::ExpSignSeen)
+            }
+            (NumberPhase::ExpSeen, '0'..='9') => NumberStep::Continue(NumberPhase::ExpDigits),
+            (NumberPhase::ExpSeen, _) => NumberStep::Invalid,
+
+            (NumberPhase::ExpSignSeen, '0'..='9') => NumberStep::Continue(NumberPhase::ExpDigits),
+            (NumberPhase::ExpSignSeen, _) => NumberStep::Invalid,
+
+            (NumberPhase::ExpDigits, '0'..='9') => NumberStep::Continue(NumberPhase::ExpDigits),
+            (NumberPhase::ExpDigits, _) => NumberStep::Terminate,
+        }
+    }
+}
+
+impl Tokenizer {
+    fn tokenize<'s>(&mut self, data: &'s str) -> Result<JValues<'s>, TokenizerErrors> {
         match self.state {
             TokenizerState::Base => self.tokenize_base(data),
             TokenizerState::ZeroCopyString => self.tokenize_zero_copy_string(data),
-            TokenizerState::StartEscaping { .. } => self.tokenize_start_escaping(data),
-            TokenizerState::CopyingString { .. } => self.tokenize_copying_string(data),
+            TokenizerState::StartEscaping => self.tokenize_start_escaping(data),
+            TokenizerState::CopyingString => self.tokenize_copying_string(data),
             TokenizerState::ReadingHex { .. } => self.tokenize_reading_hex(data),
+            TokenizerState::ExpectLowSurrogateBackslash => {
+                self.tokenize_expect_low_surrogate_backslash(data)
+            }
+            TokenizerState::ExpectLowSurrogateU => self.tokenize_expect_low_surrogate_u(data),
+            TokenizerState::ReadingNumberZeroCopy { .. } => {
+                self.tokenize_reading_number_zero_copy(data)
+            }
+            TokenizerState::ReadingNumberScratch { .. } => {
+                self.tokenize_reading_number_scratch(data)
+            }
+            TokenizerState::ReadingLiteral { .. } => self.tokenize_reading_literal(data),
         }
     }
-    fn tokenize_base(&'scratch mut self, data: &'s str) -> Result<JValues<'s>, TokenizerErrors> {
+    fn tokenize_base<'s>(&mut self, data: &'s str) -> Result<JValues<'s>, TokenizerErrors> {
         for (i, c) in data[self.index..].chars().enumerate() {
             let jt = match c {
                 '{' => JT::OpenObject,
@@ -67,6 +172,26 @@ impl<'s, 'scratch: 's> Tokenizer {
                     self.state = TokenizerState::ZeroCopyString;
                     return self.tokenize_zero_copy_string(data);
                 }
+                '-' | '0'..='9' => {
+                    self.index += i;
+                    self.state = TokenizerState::ReadingNumberZeroCopy(NumberPhase::Start);
+                    return self.tokenize_reading_number_zero_copy(data);
+                }
+                't' => {
+                    self.index += i;
+                    self.state = TokenizerState::ReadingLiteral("true", 0, JT::JTrue);
+                    return self.tokenize_reading_literal(data);
+                }
+                'f' => {
+                    self.index += i;
+                    self.state = TokenizerState::ReadingLiteral("false", 0, JT::JFalse);
+                    return self.tokenize_reading_literal(data);
+                }
+                'n' => {
+                    self.index += i;
+                    self.state = TokenizerState::ReadingLiteral("null", 0, JT::JNull);
+                    return self.tokenize_reading_literal(data);
+                }
                 c if c.is_whitespace() => JT::WhiteSpace,
                 _ => return Err(TokenizerErrors::WrongFormat(self.index + i)),
             };
@@ -76,7 +201,7 @@ impl<'s, 'scratch: 's> Tokenizer {
                     let begin = self.index + i;
                     self.index += i + 1;
                     return Ok(JValues {
-                        slice: &data[begin..(self.index)],
+                        slice: Cow::Borrowed(&data[begin..(self.index)]),
                         jt,
                     });
                 }
@@ -84,8 +209,8 @@ impl<'s, 'scratch: 's> Tokenizer {
         }
         Err(TokenizerErrors::NeedMoreData)
     }
-    fn tokenize_zero_copy_string(
-        &'scratch mut self,
+    fn tokenize_zero_copy_string<'s>(
+        &mut self,
         data: &'s str,
     ) -> Result<JValues<'s>, TokenizerErrors> {
         let begin = self.index;
@@ -95,7 +220,7 @@ impl<'s, 'scratch: 's> Tokenizer {
                     self.index = self.index + i + 1;
                     self.state = TokenizerState::Base;
                     return Ok(JValues {
-                        slice: &data[begin..self.index - 1],
+                        slice: Cow::Borrowed(&data[begin..self.index - 1]),
                         jt: JT::JString,
                     });
                 }
@@ -116,10 +241,10 @@ impl<'s, 'scratch: 's> Tokenizer {
         self.index = data.len();
         Err(TokenizerErrors::NeedMoreData)
     }
-    fn tokenize_start_escaping(
-        &'scratch mut self,
+    fn tokenize_start_escaping<'s>(
+        &mut self,
         data: &'s str,
-    ) -> Result<JValues<'scratch>, TokenizerErrors> {
+    ) -> Result<JValues<'s>, TokenizerErrors> {
         if let Some(c) = data[self.index..].chars().nth(0) {
             let to_add = match c {
                 '"' => '"',
@@ -140,22 +265,22 @@ impl<'s, 'scratch: 's> Tokenizer {
             self.scratch.push(to_add);
             self.index += 1;
             self.state = TokenizerState::CopyingString;
-            return self.tokenize_copying_string(data);
+            self.tokenize_copying_string(data)
         } else {
             Err(TokenizerErrors::NeedMoreData)
         }
     }
-    fn tokenize_copying_string(
-        &'scratch mut self,
+    fn tokenize_copying_string<'s>(
+        &mut self,
         data: &'s str,
-    ) -> Result<JValues<'scratch>, TokenizerErrors> {
+    ) -> Result<JValues<'s>, TokenizerErrors> {
         for (i, c) in data[self.index..].chars().enumerate() {
             match c {
                 '"' => {
                     self.index = self.index + i + 1;
                     self.state = TokenizerState::Base;
                     return Ok(JValues {
-                        slice: &self.scratch,
+                        slice: Cow::Owned(std::mem::take(&mut self.scratch)),
                         jt: JT::JString,
                     });
                 }
@@ -171,98 +296,567 @@ impl<'s, 'scratch: 's> Tokenizer {
         self.index = data.len();
         Err(TokenizerErrors::NeedMoreData)
     }
-    fn tokenize_reading_hex(
-        &'scratch mut self,
+    fn tokenize_reading_hex<'s>(
+        &mut self,
         data: &'s str,
-    ) -> Result<JValues<'scratch>, TokenizerErrors> {
-        unimplemented!()
+    ) -> Result<JValues<'s>, TokenizerErrors> {
+        let (mut acc, mut remaining) = match self.state {
+            TokenizerState::ReadingHex(acc, remaining) => (acc, remaining),
+            _ => unreachable!(),
+        };
+        for (i, c) in data[self.index..].chars().enumerate() {
+            let digit = match c.to_digit(16) {
+                Some(digit) => digit as u64,
+                None => return Err(TokenizerErrors::WrongEscapeSequence(self.index + i)),
+            };
+            acc = acc * 16 + digit;
+            remaining -= 1;
+            if remaining == 0 {
+                self.index += i + 1;
+                return self.finish_hex_digits(data, acc as u32);
+            }
+        }
+        self.index = data.len();
+        self.state = TokenizerState::ReadingHex(acc, remaining);
+        Err(TokenizerErrors::NeedMoreData)
+    }
+    // Resolves the four hex digits just read into either a standalone code
+    // point or, if it is a UTF-16 surrogate, into the matching half of a pair.
+    fn finish_hex_digits<'s>(
+        &mut self,
+        data: &'s str,
+        code_point: u32,
+    ) -> Result<JValues<'s>, TokenizerErrors> {
+        match self.pending_high_surrogate.take() {
+            Some(high) => {
+                if !(0xDC00..=0xDFFF).contains(&code_point) {
+                    return Err(TokenizerErrors::WrongEscapeSequence(self.index));
+                }
+                let combined = 0x10000 + ((high as u32 - 0xD800) << 10) + (code_point - 0xDC00);
+                let c = char::from_u32(combined)
+                    .ok_or(TokenizerErrors::WrongEscapeSequence(self.index))?;
+                self.scratch.push(c);
+                self.state = TokenizerState::CopyingString;
+                self.tokenize_copying_string(data)
+            }
+            None if (0xD800..=0xDBFF).contains(&code_point) => {
+                self.pending_high_surrogate = Some(code_point as u16);
+                self.state = TokenizerState::ExpectLowSurrogateBackslash;
+                self.tokenize_expect_low_surrogate_backslash(data)
+            }
+            None if (0xDC00..=0xDFFF).contains(&code_point) => {
+                Err(TokenizerErrors::WrongEscapeSequence(self.index))
+            }
+            None => {
+                let c = char::from_u32(code_point)
+                    .ok_or(TokenizerErrors::WrongEscapeSequence(self.index))?;
+                self.scratch.push(c);
+                self.state = TokenizerState::CopyingString;
+                self.tokenize_copying_string(data)
+            }
+        }
+    }
+    fn tokenize_expect_low_surrogate_backslash<'s>(
+        &mut self,
+        data: &'s str,
+    ) -> Result<JValues<'s>, TokenizerErrors> {
+        match data[self.index..].chars().next() {
+            Some('\\') => {
+                self.index += 1;
+                self.state = TokenizerState::ExpectLowSurrogateU;
+                self.tokenize_expect_low_surrogate_u(data)
+            }
+            Some(_) => Err(TokenizerErrors::WrongEscapeSequence(self.index)),
+            None => Err(TokenizerErrors::NeedMoreData),
+        }
+    }
+    fn tokenize_expect_low_surrogate_u<'s>(
+        &mut self,
+        data: &'s str,
+    ) -> Result<JValues<'s>, TokenizerErrors> {
+        match data[self.index..].chars().next() {
+            Some('u') => {
+                self.index += 1;
+                self.state = TokenizerState::ReadingHex(0, 4);
+                self.tokenize_reading_hex(data)
+            }
+            Some(_) => Err(TokenizerErrors::WrongEscapeSequence(self.index)),
+            None => Err(TokenizerErrors::NeedMoreData),
+        }
+    }
+    fn tokenize_reading_number_zero_copy<'s>(
+        &mut self,
+        data: &'s str,
+    ) -> Result<JValues<'s>, TokenizerErrors> {
+        let mut phase = match self.state {
+            TokenizerState::ReadingNumberZeroCopy(phase) => phase,
+            _ => unreachable!(),
+        };
+        let begin = self.index;
+        for (i, c) in data[begin..].chars().enumerate() {
+            match phase.step(c) {
+                NumberStep::Continue(next) => phase = next,
+                NumberStep::Terminate => {
+                    if !phase.is_complete() {
+                        return Err(TokenizerErrors::WrongFormat(begin + i));
+                    }
+                    self.index = begin + i;
+                    self.state = TokenizerState::Base;
+                    return Ok(JValues {
+                        slice: Cow::Borrowed(&data[begin..self.index]),
+                        jt: JT::JNumber,
+                    });
+                }
+                NumberStep::Invalid => return Err(TokenizerErrors::WrongFormat(begin + i)),
+            }
+        }
+        self.scratch.truncate(0);
+        self.scratch.push_str(&data[begin..]);
+        self.state = TokenizerState::ReadingNumberScratch(phase);
+        self.index = data.len();
+        Err(TokenizerErrors::NeedMoreData)
+    }
+    fn tokenize_reading_number_scratch<'s>(
+        &mut self,
+        data: &'s str,
+    ) -> Result<JValues<'s>, TokenizerErrors> {
+        let mut phase = match self.state {
+            TokenizerState::ReadingNumberScratch(phase) => phase,
+            _ => unreachable!(),
+        };
+        for (i, c) in data[self.index..].chars().enumerate() {
+            match phase.step(c) {
+                NumberStep::Continue(next) => phase = next,
+                NumberStep::Terminate => {
+                    if !phase.is_complete() {
+                        return Err(TokenizerErrors::WrongFormat(self.index + i));
+                    }
+                    self.index += i;
+                    self.state = TokenizerState::Base;
+                    return Ok(JValues {
+                        slice: Cow::Owned(std::mem::take(&mut self.scratch)),
+                        jt: JT::JNumber,
+                    });
+                }
+                NumberStep::Invalid => return Err(TokenizerErrors::WrongFormat(self.index + i)),
+            }
+            self.scratch.push(c);
+        }
+        self.index = data.len();
+        self.state = TokenizerState::ReadingNumberScratch(phase);
+        Err(TokenizerErrors::NeedMoreData)
+    }
+    fn tokenize_reading_literal<'s>(
+        &mut self,
+        data: &'s str,
+    ) -> Result<JValues<'s>, TokenizerErrors> {
+        let (literal, mut matched, jt) = match self.state {
+            TokenizerState::ReadingLiteral(literal, matched, jt) => (literal, matched, jt),
+            _ => unreachable!(),
+        };
+        for (i, c) in data[self.index..].chars().enumerate() {
+            let expected = literal[matched..].chars().next().unwrap();
+            if c != expected {
+                return Err(TokenizerErrors::WrongFormat(self.index + i));
+            }
+            matched += 1;
+            if matched == literal.len() {
+                self.index += i + 1;
+                self.state = TokenizerState::Base;
+                return Ok(JValues {
+                    slice: Cow::Borrowed(literal),
+                    jt,
+                });
+            }
+        }
+        self.index = data.len();
+        self.state = TokenizerState::ReadingLiteral(literal, matched, jt);
+        Err(TokenizerErrors::NeedMoreData)
     }
 }
 
 #[derive(Debug, PartialEq)]
 enum F {
-    InObject,
-    InObjectAfterKey,
-    InArray,
-    Key,
-    JString,
-    JNumber,
+    Object,
+    ObjectAfterComma,
+    ObjectAfterKey,
+    ObjectAfterColon,
+    ObjectAfterValue,
+    Array,
+    ArrayAfterComma,
+    ArrayAfterValue,
 }
 
-enum PE {
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
     EndOfData,
     NeedMoreData,
     WrongEscapeSequence(usize),
     WrongFormat(usize),
 }
 
-impl From<TokenizerErrors> for PE {
+impl From<TokenizerErrors> for ParseError {
     fn from(error: TokenizerErrors) -> Self {
         match error {
-            TokenizerErrors::EndOfData => PE::EndOfData,
-            TokenizerErrors::NeedMoreData => PE::NeedMoreData,
-            TokenizerErrors::WrongEscapeSequence(u) => PE::WrongEscapeSequence(u),
-            TokenizerErrors::WrongFormat(u) => PE::WrongFormat(u),
+            TokenizerErrors::NeedMoreData => ParseError::NeedMoreData,
+            TokenizerErrors::WrongEscapeSequence(u) => ParseError::WrongEscapeSequence(u),
+            TokenizerErrors::WrongFormat(u) => ParseError::WrongFormat(u),
         }
     }
 }
 
-struct Parser {
+pub struct Parser {
     stack: std::vec::Vec<F>,
     tokenizer: Tokenizer,
+    done: bool,
 }
-type PR<'s> = (F, Option<&'s str>);
 
 enum PR2<'s> {
-    InObject,
-    InArray,
-    Key(&'s str),
-    JString(&'s str),
+    Object,
+    EndObject,
+    Array,
+    EndArray,
+    Key(Cow<'s, str>),
+    JString(Cow<'s, str>),
+    JNumber(Cow<'s, str>),
+    JTrue,
+    JFalse,
+    JNull,
+}
+
+// Events emitted by `Parser::feed` as it drives the tokenizer/parser state
+// machine over a chunk of a streamed JSON document.
+#[derive(Debug, PartialEq)]
+pub enum Event<'s> {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+    Key(Cow<'s, str>),
+    String(Cow<'s, str>),
+    Number(Cow<'s, str>),
+    Bool(bool),
+    Null,
 }
 
-impl<'s, 'ss: 's> Parser {
-    fn parse(&'ss mut self, data: &'s str) -> Result<PR<'s>, PE> {
+impl Parser {
+    pub fn new() -> Self {
+        Parser {
+            stack: std::vec::Vec::new(),
+            tokenizer: Tokenizer {
+                scratch: std::string::String::new(),
+                state: TokenizerState::Base,
+                index: 0,
+                pending_high_surrogate: None,
+            },
+            done: false,
+        }
+    }
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Parser {
+    // Feeds one chunk of a streamed document, calling `emit` for every event
+    // completed while draining it. A token split across chunks is resumed
+    // transparently by the tokenizer's own internal scratch buffer, so a
+    // chunk boundary never needs to be tracked here; we just reset the
+    // index to scan `chunk` from its start and stop as soon as the
+    // tokenizer asks for more data than this chunk has left.
+    //
+    // `'c` is named explicitly and shared between `chunk` and `emit`'s
+    // `Event<'c>` parameter: leaving it elided would make `impl
+    // FnMut(Event)` a higher-ranked bound over its own fresh lifetime on
+    // every call, independent of `chunk`, and no borrowed event could then
+    // escape the closure into an outer container.
+    pub fn feed<'c>(&mut self, chunk: &'c str, mut emit: impl FnMut(Event<'c>)) -> Result<(), ParseError> {
+        self.tokenizer.index = 0;
+        loop {
+            match self.parse(chunk) {
+                Ok(None) => continue,
+                Ok(Some(pr2)) => emit(Self::to_event(pr2)),
+                Err(ParseError::NeedMoreData) => return Ok(()),
+                // The root value is complete; anything left in this chunk
+                // had better be whitespace, or it's data trailing the
+                // document (e.g. "true garbage", "01", "{}{}") that the
+                // grammar only allows a single root value to be followed
+                // by.
+                Err(ParseError::EndOfData) => {
+                    let rest = &chunk[self.tokenizer.index..];
+                    return match rest.find(|c: char| !c.is_whitespace()) {
+                        Some(offset) => Err(ParseError::WrongFormat(self.tokenizer.index + offset)),
+                        None => Ok(()),
+                    };
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+    // Signals that no further chunks are coming, flushing a root-level
+    // number left buffered in the tokenizer's scratch space. Numbers are
+    // the one token with no delimiter of their own — unlike a string's
+    // closing quote or true/false/null's exact literal match, the
+    // tokenizer can't tell a number is finished until it sees a
+    // non-digit/exponent character after it, so `feed` alone can never
+    // distinguish "the document ended here" from "the rest is in the next
+    // chunk". Only call this once the caller itself knows there is no
+    // next chunk.
+    pub fn finish(&mut self) -> Result<Option<Event<'static>>, ParseError> {
+        if self.done {
+            return Ok(None);
+        }
+        match self.tokenizer.state {
+            TokenizerState::ReadingNumberScratch(phase)
+                if self.stack.is_empty() && phase.is_complete() =>
+            {
+                self.tokenizer.state = TokenizerState::Base;
+                self.done = true;
+                Ok(Some(Event::Number(Cow::Owned(std::mem::take(
+                    &mut self.tokenizer.scratch,
+                )))))
+            }
+            _ => Err(ParseError::NeedMoreData),
+        }
+    }
+    fn to_event<'a>(pr2: PR2<'a>) -> Event<'a> {
+        match pr2 {
+            PR2::Object => Event::StartObject,
+            PR2::EndObject => Event::EndObject,
+            PR2::Array => Event::StartArray,
+            PR2::EndArray => Event::EndArray,
+            PR2::Key(s) => Event::Key(s),
+            PR2::JString(s) => Event::String(s),
+            PR2::JNumber(s) => Event::Number(s),
+            PR2::JTrue => Event::Bool(true),
+            PR2::JFalse => Event::Bool(false),
+            PR2::JNull => Event::Null,
+        }
+    }
+}
+
+impl Parser {
+    // Sets the frame just below the top of the stack (or the top itself, if
+    // the stack is empty at the root) to reflect what we now expect next.
+    fn set_top(&mut self, frame: F) {
+        if let Some(top) = self.stack.last_mut() {
+            *top = frame;
+        }
+    }
+    // Pops the container we just closed. If that empties the stack we have
+    // finished the single root value the grammar allows.
+    fn close_container(&mut self) {
+        self.stack.pop();
+        if self.stack.is_empty() {
+            self.done = true;
+        }
+    }
+    // Advances the state machine by (at most) one tokenizer call. `Ok(None)`
+    // means a token was consumed but it carries no event of its own (a
+    // structural ':' or ',') — the caller (`feed`'s loop) just calls us
+    // again rather than us recursing, which is what lets `parse` take a
+    // plain `&mut self` instead of pinning its borrow to the data lifetime
+    // across the whole call chain.
+    fn parse<'s>(&mut self, data: &'s str) -> Result<Option<PR2<'s>>, ParseError> {
+        if self.done {
+            return Err(ParseError::EndOfData);
+        }
         let index = self.tokenizer.index;
         let token = self.tokenizer.tokenize(data)?;
-        let state = self.stack.last();
-        let result = match (state, token.jt) {
+        let top = self.stack.last();
+        let result = match (top, token.jt) {
+            // Root: exactly one value, array, or object is expected.
             (None, JT::OpenObject) => {
-                self.stack.push(F::InObject);
-                (F::InObject, None)
+                self.stack.push(F::Object);
+                PR2::Object
             }
             (None, JT::OpenArray) => {
-                self.stack.push(F::InArray);
-                (F::InArray, None)
+                self.stack.push(F::Array);
+                PR2::Array
+            }
+            (None, JT::JString) => {
+                self.done = true;
+                PR2::JString(token.slice)
+            }
+            (None, JT::JNumber) => {
+                self.done = true;
+                PR2::JNumber(token.slice)
+            }
+            (None, JT::JTrue) => {
+                self.done = true;
+                PR2::JTrue
+            }
+            (None, JT::JFalse) => {
+                self.done = true;
+                PR2::JFalse
+            }
+            (None, JT::JNull) => {
+                self.done = true;
+                PR2::JNull
+            }
+            (None, _) => return Err(ParseError::WrongFormat(index)),
+
+            // Just opened '{' (or emptied by a previous object): a key or '}'.
+            (Some(F::Object), JT::JString) => {
+                self.set_top(F::ObjectAfterKey);
+                PR2::Key(token.slice)
+            }
+            (Some(F::Object), JT::CloseObject) => {
+                self.close_container();
+                PR2::EndObject
+            }
+            (Some(F::Object), _) => return Err(ParseError::WrongFormat(index)),
+
+            // After a ',' inside an object: a key is mandatory, no '}' here.
+            (Some(F::ObjectAfterComma), JT::JString) => {
+                self.set_top(F::ObjectAfterKey);
+                PR2::Key(token.slice)
             }
-            (None, JT::JString) => (F::JString, Some(token.slice)),
-            (None, JT::JNumber) => (F::JNumber, None),
-            (None, _) => return Err(PE::WrongFormat(index)),
+            (Some(F::ObjectAfterComma), _) => return Err(ParseError::WrongFormat(index)),
 
-            (Some(F::InObject), JT::JString) => {
-                self.stack.push(F::Key);
-                (F::JString, Some(token.slice))
+            // After a key: only ':' is valid; it carries no event of its own.
+            (Some(F::ObjectAfterKey), JT::Colon) => {
+                self.set_top(F::ObjectAfterColon);
+                return Ok(None);
             }
-            /*
-            (Some(F::Key), JT::Comma) => {
-                self.stack.push(F::InObjectAfterKey);
-                return self.parse(data);
+            (Some(F::ObjectAfterKey), _) => return Err(ParseError::WrongFormat(index)),
+
+            // After ':': any value is expected.
+            (Some(F::ObjectAfterColon), JT::OpenObject) => {
+                self.set_top(F::ObjectAfterValue);
+                self.stack.push(F::Object);
+                PR2::Object
+            }
+            (Some(F::ObjectAfterColon), JT::OpenArray) => {
+                self.set_top(F::ObjectAfterValue);
+                self.stack.push(F::Array);
+                PR2::Array
+            }
+            (Some(F::ObjectAfterColon), JT::JString) => {
+                self.set_top(F::ObjectAfterValue);
+                PR2::JString(token.slice)
+            }
+            (Some(F::ObjectAfterColon), JT::JNumber) => {
+                self.set_top(F::ObjectAfterValue);
+                PR2::JNumber(token.slice)
+            }
+            (Some(F::ObjectAfterColon), JT::JTrue) => {
+                self.set_top(F::ObjectAfterValue);
+                PR2::JTrue
+            }
+            (Some(F::ObjectAfterColon), JT::JFalse) => {
+                self.set_top(F::ObjectAfterValue);
+                PR2::JFalse
+            }
+            (Some(F::ObjectAfterColon), JT::JNull) => {
+                self.set_top(F::ObjectAfterValue);
+                PR2::JNull
+            }
+            (Some(F::ObjectAfterColon), _) => return Err(ParseError::WrongFormat(index)),
+
+            // After a member value: ',' (another member) or '}' (done).
+            (Some(F::ObjectAfterValue), JT::Comma) => {
+                self.set_top(F::ObjectAfterComma);
+                return Ok(None);
+            }
+            (Some(F::ObjectAfterValue), JT::CloseObject) => {
+                self.close_container();
+                PR2::EndObject
+            }
+            (Some(F::ObjectAfterValue), _) => return Err(ParseError::WrongFormat(index)),
+
+            // Just opened '[' (or emptied by a previous array): a value or ']'.
+            (Some(F::Array), JT::CloseArray) => {
+                self.close_container();
+                PR2::EndArray
+            }
+            (Some(F::Array), JT::OpenObject) => {
+                self.set_top(F::ArrayAfterValue);
+                self.stack.push(F::Object);
+                PR2::Object
+            }
+            (Some(F::Array), JT::OpenArray) => {
+                self.set_top(F::ArrayAfterValue);
+                self.stack.push(F::Array);
+                PR2::Array
+            }
+            (Some(F::Array), JT::JString) => {
+                self.set_top(F::ArrayAfterValue);
+                PR2::JString(token.slice)
+            }
+            (Some(F::Array), JT::JNumber) => {
+                self.set_top(F::ArrayAfterValue);
+                PR2::JNumber(token.slice)
+            }
+            (Some(F::Array), JT::JTrue) => {
+                self.set_top(F::ArrayAfterValue);
+                PR2::JTrue
+            }
+            (Some(F::Array), JT::JFalse) => {
+                self.set_top(F::ArrayAfterValue);
+                PR2::JFalse
             }
-            */
-            (Some(F::InObjectAfterKey), JT::JString) => {
-                self.stack.pop();
-                (F::JString, Some(token.slice))
+            (Some(F::Array), JT::JNull) => {
+                self.set_top(F::ArrayAfterValue);
+                PR2::JNull
             }
+            (Some(F::Array), _) => return Err(ParseError::WrongFormat(index)),
 
-            (_, _) => unimplemented!(),
+            // After a ',' inside an array: a value is mandatory, no ']' here.
+            (Some(F::ArrayAfterComma), JT::OpenObject) => {
+                self.set_top(F::ArrayAfterValue);
+                self.stack.push(F::Object);
+                PR2::Object
+            }
+            (Some(F::ArrayAfterComma), JT::OpenArray) => {
+                self.set_top(F::ArrayAfterValue);
+                self.stack.push(F::Array);
+                PR2::Array
+            }
+            (Some(F::ArrayAfterComma), JT::JString) => {
+                self.set_top(F::ArrayAfterValue);
+                PR2::JString(token.slice)
+            }
+            (Some(F::ArrayAfterComma), JT::JNumber) => {
+                self.set_top(F::ArrayAfterValue);
+                PR2::JNumber(token.slice)
+            }
+            (Some(F::ArrayAfterComma), JT::JTrue) => {
+                self.set_top(F::ArrayAfterValue);
+                PR2::JTrue
+            }
+            (Some(F::ArrayAfterComma), JT::JFalse) => {
+                self.set_top(F::ArrayAfterValue);
+                PR2::JFalse
+            }
+            (Some(F::ArrayAfterComma), JT::JNull) => {
+                self.set_top(F::ArrayAfterValue);
+                PR2::JNull
+            }
+            (Some(F::ArrayAfterComma), _) => return Err(ParseError::WrongFormat(index)),
+
+            // After an array value: ',' (another element) or ']' (done).
+            (Some(F::ArrayAfterValue), JT::Comma) => {
+                self.set_top(F::ArrayAfterComma);
+                return Ok(None);
+            }
+            (Some(F::ArrayAfterValue), JT::CloseArray) => {
+                self.close_container();
+                PR2::EndArray
+            }
+            (Some(F::ArrayAfterValue), _) => return Err(ParseError::WrongFormat(index)),
         };
-        Ok(result)
+        Ok(Some(result))
     }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::{Tokenizer, TokenizerErrors, TokenizerState, JT};
+    use crate::{Event, ParseError, Parser, Tokenizer, TokenizerErrors, TokenizerState, JT};
 
     #[test]
     fn tokenizer2_open_close_curly() {
@@ -270,6 +864,7 @@ mod tests {
             scratch: std::string::String::new(),
             state: TokenizerState::Base,
             index: 0,
+            pending_high_surrogate: None,
         };
         let data = "{}";
         let open = tokenizer.tokenize(data).unwrap();
@@ -288,6 +883,7 @@ mod tests {
             scratch: std::string::String::new(),
             state: TokenizerState::Base,
             index: 0,
+            pending_high_surrogate: None,
         };
         let data = "    \"foo_ _bar\"  ";
         let string = tokenizer.tokenize(data).unwrap();
@@ -301,6 +897,7 @@ mod tests {
             scratch: std::string::String::new(),
             state: TokenizerState::Base,
             index: 0,
+            pending_high_surrogate: None,
         };
         let data = "    \"foo";
         let string = tokenizer.tokenize(data);
@@ -352,4 +949,267 @@ mod tests {
         assert_eq!(different_string_escape.slice, "foo\nbar");
     }
 
+    #[test]
+    fn tokenize_unicode_escape() {
+        let mut tokenizer = Tokenizer {
+            scratch: std::string::String::new(),
+            state: TokenizerState::Base,
+            index: 0,
+            pending_high_surrogate: None,
+        };
+        let data = "\"snow\\u2603man\"";
+        let string = tokenizer.tokenize(data).unwrap();
+        assert_eq!(string.jt, JT::JString);
+        assert_eq!(string.slice, "snow\u{2603}man");
+
+        tokenizer.index = 0;
+        let data = "\"\\ud83d\\ude00\"";
+        let string = tokenizer.tokenize(data).unwrap();
+        assert_eq!(string.jt, JT::JString);
+        assert_eq!(string.slice, "\u{1F600}");
+
+        tokenizer.index = 0;
+        let data = "\"\\ud83d\\u0041\"";
+        let err = tokenizer.tokenize(data);
+        assert_eq!(
+            TokenizerErrors::WrongEscapeSequence(13),
+            err.err().unwrap()
+        );
+
+        tokenizer.index = 0;
+        let data = "\"\\ude00\"";
+        let err = tokenizer.tokenize(data);
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn tokenize_numbers() {
+        let mut tokenizer = Tokenizer {
+            scratch: std::string::String::new(),
+            state: TokenizerState::Base,
+            index: 0,
+            pending_high_surrogate: None,
+        };
+        let data = "0 -12 3.14 -0.5 1e10 2E-3 10,";
+        let zero = tokenizer.tokenize(data).unwrap();
+        assert_eq!(zero.jt, JT::JNumber);
+        assert_eq!(zero.slice, "0");
+
+        let neg = tokenizer.tokenize(data).unwrap();
+        assert_eq!(neg.jt, JT::JNumber);
+        assert_eq!(neg.slice, "-12");
+
+        let frac = tokenizer.tokenize(data).unwrap();
+        assert_eq!(frac.jt, JT::JNumber);
+        assert_eq!(frac.slice, "3.14");
+
+        let neg_frac = tokenizer.tokenize(data).unwrap();
+        assert_eq!(neg_frac.jt, JT::JNumber);
+        assert_eq!(neg_frac.slice, "-0.5");
+
+        let exp = tokenizer.tokenize(data).unwrap();
+        assert_eq!(exp.jt, JT::JNumber);
+        assert_eq!(exp.slice, "1e10");
+
+        let neg_exp = tokenizer.tokenize(data).unwrap();
+        assert_eq!(neg_exp.jt, JT::JNumber);
+        assert_eq!(neg_exp.slice, "2E-3");
+
+        let before_comma = tokenizer.tokenize(data).unwrap();
+        assert_eq!(before_comma.jt, JT::JNumber);
+        assert_eq!(before_comma.slice, "10");
+
+        let comma = tokenizer.tokenize(data).unwrap();
+        assert_eq!(comma.jt, JT::Comma);
+    }
+
+    #[test]
+    fn tokenize_number_multiple_buffers() {
+        let mut tokenizer = Tokenizer {
+            scratch: std::string::String::new(),
+            state: TokenizerState::Base,
+            index: 0,
+            pending_high_surrogate: None,
+        };
+        let data = "12.";
+        let err = tokenizer.tokenize(data);
+        assert_eq!(TokenizerErrors::NeedMoreData, err.err().unwrap());
+
+        tokenizer.index = 0;
+        let data = "34 ";
+        let number = tokenizer.tokenize(data).unwrap();
+        assert_eq!(number.jt, JT::JNumber);
+        assert_eq!(number.slice, "12.34");
+    }
+
+    #[test]
+    fn tokenize_literals() {
+        let mut tokenizer = Tokenizer {
+            scratch: std::string::String::new(),
+            state: TokenizerState::Base,
+            index: 0,
+            pending_high_surrogate: None,
+        };
+        let data = "true false null";
+        let t = tokenizer.tokenize(data).unwrap();
+        assert_eq!(t.jt, JT::JTrue);
+        assert_eq!(t.slice, "true");
+
+        let f = tokenizer.tokenize(data).unwrap();
+        assert_eq!(f.jt, JT::JFalse);
+        assert_eq!(f.slice, "false");
+
+        let n = tokenizer.tokenize(data).unwrap();
+        assert_eq!(n.jt, JT::JNull);
+        assert_eq!(n.slice, "null");
+    }
+
+    #[test]
+    fn tokenize_literal_multiple_buffers() {
+        let mut tokenizer = Tokenizer {
+            scratch: std::string::String::new(),
+            state: TokenizerState::Base,
+            index: 0,
+            pending_high_surrogate: None,
+        };
+        let data = "tr";
+        let err = tokenizer.tokenize(data);
+        assert_eq!(TokenizerErrors::NeedMoreData, err.err().unwrap());
+
+        tokenizer.index = 0;
+        let data = "ue";
+        let t = tokenizer.tokenize(data).unwrap();
+        assert_eq!(t.jt, JT::JTrue);
+        assert_eq!(t.slice, "true");
+
+        tokenizer.index = 0;
+        let data = "nul";
+        let err = tokenizer.tokenize(data);
+        assert_eq!(TokenizerErrors::NeedMoreData, err.err().unwrap());
+
+        tokenizer.index = 0;
+        let data = "xyz";
+        let err = tokenizer.tokenize(data);
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn feed_emits_string_event() {
+        let mut parser = Parser::new();
+        let mut events = Vec::new();
+        parser
+            .feed("\"hi\"", |event| events.push(event))
+            .unwrap();
+        assert_eq!(events, vec![Event::String("hi".into())]);
+    }
+
+    #[test]
+    fn feed_emits_start_object_and_waits_for_more_data() {
+        let mut parser = Parser::new();
+        let mut events = Vec::new();
+        parser.feed("{", |event| events.push(event)).unwrap();
+        assert_eq!(events, vec![Event::StartObject]);
+    }
+
+    #[test]
+    fn feed_buffers_a_chunk_split_mid_token() {
+        let mut parser = Parser::new();
+        let mut events = Vec::new();
+        parser.feed("\"par", |event| events.push(event)).unwrap();
+        assert!(events.is_empty());
+        parser.feed("tial\"", |event| events.push(event)).unwrap();
+        assert_eq!(events, vec![Event::String("partial".into())]);
+    }
+
+    #[test]
+    fn feed_emits_a_full_nested_document() {
+        let mut parser = Parser::new();
+        let mut events = Vec::new();
+        parser
+            .feed(
+                "{\"a\":1,\"b\":[true,false,null]}",
+                |event| events.push(event),
+            )
+            .unwrap();
+        assert_eq!(
+            events,
+            vec![
+                Event::StartObject,
+                Event::Key("a".into()),
+                Event::Number("1".into()),
+                Event::Key("b".into()),
+                Event::StartArray,
+                Event::Bool(true),
+                Event::Bool(false),
+                Event::Null,
+                Event::EndArray,
+                Event::EndObject,
+            ]
+        );
+    }
+
+    #[test]
+    fn feed_rejects_trailing_comma() {
+        let mut parser = Parser::new();
+        let mut events = Vec::new();
+        let result = parser.feed("{\"a\":1,}", |event| events.push(event));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn feed_rejects_unbalanced_brackets() {
+        let mut parser = Parser::new();
+        let mut events = Vec::new();
+        let result = parser.feed("{\"a\":1]", |event| events.push(event));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn feed_emits_a_bare_scalar_and_then_ends() {
+        // A bare number has no delimiter telling `feed` it's complete (it
+        // could always be continued by the next chunk), so finishing one
+        // with nothing after it requires the explicit `finish` call.
+        let mut parser = Parser::new();
+        let mut events = Vec::new();
+        parser.feed("42", |event| events.push(event)).unwrap();
+        assert!(events.is_empty());
+        let last = parser.finish().unwrap();
+        assert_eq!(last, Some(Event::Number("42".into())));
+    }
+
+    #[test]
+    fn feed_rejects_trailing_non_whitespace_after_a_literal() {
+        let mut parser = Parser::new();
+        let mut events = Vec::new();
+        let result = parser.feed("true garbage", |event| events.push(event));
+        assert_eq!(result, Err(ParseError::WrongFormat(5)));
+        assert_eq!(events, vec![Event::Bool(true)]);
+    }
+
+    #[test]
+    fn feed_rejects_a_second_digit_after_a_leading_zero() {
+        let mut parser = Parser::new();
+        let mut events = Vec::new();
+        let result = parser.feed("01", |event| events.push(event));
+        assert_eq!(result, Err(ParseError::WrongFormat(1)));
+        assert_eq!(events, vec![Event::Number("0".into())]);
+    }
+
+    #[test]
+    fn feed_rejects_a_second_root_value() {
+        let mut parser = Parser::new();
+        let mut events = Vec::new();
+        let result = parser.feed("{}{}", |event| events.push(event));
+        assert_eq!(result, Err(ParseError::WrongFormat(2)));
+        assert_eq!(events, vec![Event::StartObject, Event::EndObject]);
+    }
+
+    #[test]
+    fn feed_allows_trailing_whitespace_after_the_root_value() {
+        let mut parser = Parser::new();
+        let mut events = Vec::new();
+        parser.feed("true  \n", |event| events.push(event)).unwrap();
+        assert_eq!(events, vec![Event::Bool(true)]);
+    }
+
 }